@@ -0,0 +1,40 @@
+use std::fmt::{self, Write};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use u64_id::U64Id;
+
+/// The old `Display`, which went through `write!`.
+struct OldDisplay(u64);
+impl fmt::Display for OldDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "*{:x}", self.0)
+    }
+}
+
+fn display(c: &mut Criterion) {
+    let ids: Vec<u64> = (0..1024u64)
+        .map(|i| i.wrapping_mul(0x9e3779b97f4a7c15))
+        .collect();
+    let mut buf = String::with_capacity(32);
+
+    c.bench_function("display", |b| {
+        b.iter(|| {
+            for &id in &ids {
+                buf.clear();
+                write!(buf, "{}", black_box(U64Id::id(id))).unwrap();
+            }
+        })
+    });
+
+    c.bench_function("display_write_macro", |b| {
+        b.iter(|| {
+            for &id in &ids {
+                buf.clear();
+                write!(buf, "{}", black_box(OldDisplay(id))).unwrap();
+            }
+        })
+    });
+}
+
+criterion_group!(benches, display);
+criterion_main!(benches);
@@ -0,0 +1,24 @@
+//! C ABI functions, so C and C++ code can share the same rules for ids.
+//!
+//! Since [U64Id] is `#[repr(transparent)]`, these take and return the raw `u64`.
+
+use crate::U64Id;
+
+/// Checks if the raw id is `NULL`. See [U64Id::is_null].
+#[no_mangle]
+pub extern "C" fn u64id_is_null(id: u64) -> bool {
+    U64Id::id(id).is_null()
+}
+
+/// Checks if the raw id is valid, which is to say, not `NULL`.
+#[no_mangle]
+pub extern "C" fn u64id_is_valid(id: u64) -> bool {
+    !U64Id::id(id).is_null()
+}
+
+/// Creates a new random id. See [U64Id::new].
+#[cfg(feature = "rand")]
+#[no_mangle]
+pub extern "C" fn u64id_new() -> u64 {
+    U64Id::new().inner()
+}
@@ -1,46 +1,1484 @@
 use core::fmt;
 
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "wasm")]
+mod wasm;
+
 /// An ID for Assets. Currently, it is implemented as a wrapper around u64s.
 /// If a collision occurs in your own code, please file an issue -- this will
 /// likely require several billion assets for that to happen though.
+///
+/// `U64Id` is `#[repr(transparent)]` over a `u64`, so it is guaranteed to have
+/// exactly the same size, alignment, and ABI as a `u64`. Unsafe code (FFI, mmap,
+/// `bytemuck`-style casts) may rely on this.
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
 #[repr(transparent)]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
 pub struct U64Id(u64);
 
-impl U64Id {
-    /// Returns the forbidden `null` id.
-    pub const NULL: U64Id = U64Id(0);
+const _: () = assert!(core::mem::size_of::<U64Id>() == 8);
+const _: () = assert!(core::mem::align_of::<U64Id>() == core::mem::align_of::<u64>());
+
+impl U64Id {
+    /// Returns the forbidden `null` id.
+    pub const NULL: U64Id = U64Id(0);
+
+    /// The size of a `U64Id` in bytes. This is always 8.
+    pub const SIZE: usize = core::mem::size_of::<U64Id>();
+
+    /// The number of bits of entropy in a random id, rounded down. Every value but
+    /// `NULL` can be generated, which leaves `2^64 - 1` possibilities, or 63 whole bits.
+    pub const VALID_BITS: u32 = u64::MAX.ilog2();
+
+    /// The most characters plain hex can take for an id, as with `u64::MAX`. `Display`
+    /// adds a leading `*` on top of this.
+    pub const HEX_MAX_LEN: usize = 16;
+
+    /// The most characters [to_base58](Self::to_base58) can produce.
+    pub const BASE58_MAX_LEN: usize = 11;
+
+    /// The number of characters [to_base32](Self::to_base32) produces. base32 is fixed
+    /// width, so this is exact for every id.
+    pub const BASE32_LEN: usize = 13;
+
+    /// Creates a new, random AssetId, seeded cheaply from thread_rng.
+    ///
+    /// To avoid calling this internal function repeatedly, consider using [from_rng](Self::from_rng)
+    /// with your own rng handler.
+    ///
+    /// Internally, we use a u64 for random numbers. These have been, generally,
+    /// large enough. This never returns `NULL`.
+    #[cfg(feature = "rand")]
+    pub fn new() -> Self {
+        Self::from_rng(&mut rand::thread_rng())
+    }
+
+    /// Creates a new, random AssetId using the given rng. This never returns `NULL`.
+    #[cfg(feature = "rand")]
+    pub fn from_rng<R: rand::Rng + ?Sized>(rng: &mut R) -> Self {
+        let output = Self(rng.gen_range(1..=u64::MAX));
+        debug_assert!(!output.is_null(), "random ids must never be null");
+
+        output
+    }
+
+    /// Creates two random ids which are guaranteed to be different from each other.
+    ///
+    /// A collision is astronomically unlikely, but rather than hoping, we just regenerate
+    /// the second id until it differs. Handy for the two ends of an edge in tests.
+    #[cfg(feature = "rand")]
+    pub fn random_distinct_pair<R: rand::Rng + ?Sized>(rng: &mut R) -> (Self, Self) {
+        let first = Self::from_rng(rng);
+        let mut second = Self::from_rng(rng);
+        while second == first {
+            second = Self::from_rng(rng);
+        }
+
+        (first, second)
+    }
+
+    /// Creates a random id whose top `prefix_bits` bits are `prefix`, such as a tenant
+    /// id, with the rest filled from `rng`. This never returns `NULL`, unless you pass
+    /// a 64 bit prefix of 0 and leave no room for randomness.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_bits` is more than 64, or if `prefix` doesn't fit in `prefix_bits`.
+    #[cfg(feature = "rand")]
+    pub fn new_with_prefix<R: rand::Rng + ?Sized>(
+        rng: &mut R,
+        prefix: u64,
+        prefix_bits: u32,
+    ) -> Self {
+        assert!(prefix_bits <= 64, "prefix_bits must be at most 64");
+        assert!(
+            prefix.checked_shr(prefix_bits).unwrap_or(0) == 0,
+            "prefix {prefix:#x} does not fit in {prefix_bits} bits"
+        );
+
+        let random_bits = 64 - prefix_bits;
+        if random_bits == 0 {
+            return Self(prefix);
+        }
+
+        let mask = u64::MAX >> prefix_bits;
+        // with a zero prefix, an all zero tail would be NULL
+        let tail = if prefix == 0 {
+            rng.gen_range(1..=mask)
+        } else {
+            rng.gen::<u64>() & mask
+        };
+
+        Self(prefix.checked_shl(random_bits).unwrap_or(0) | tail)
+    }
+
+    /// Creates a new, time-sortable id, similar in spirit to a UUIDv7.
+    ///
+    /// The top 44 bits hold the milliseconds since the unix epoch, read from the
+    /// system clock, and the bottom 20 bits are filled from `rng`. Ids made in
+    /// different milliseconds sort by creation time. Within a single millisecond,
+    /// only the 20 random bits distinguish ids, so around a thousand ids per
+    /// millisecond gives roughly a 40% chance of a collision. If you need more than
+    /// that, this is not the constructor for you.
+    #[cfg(feature = "rand")]
+    pub fn new_v7<R: rand::Rng>(rng: &mut R) -> Self {
+        let unix_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|v| v.as_millis() as u64)
+            .unwrap_or_default();
+        let tail = rng.gen::<u64>() & ((1 << V7_RANDOM_BITS) - 1);
+
+        Self((unix_ms << V7_RANDOM_BITS) | tail)
+    }
+
+    /// Decodes the creation time of an id made with [new_v7](Self::new_v7), given the
+    /// epoch its timestamp counts from. For `new_v7` itself, that's `UNIX_EPOCH`.
+    ///
+    /// The result is only accurate to the millisecond.
+    pub fn system_time(self, epoch: std::time::SystemTime) -> std::time::SystemTime {
+        epoch + self.duration_since_epoch(64 - V7_RANDOM_BITS)
+    }
+
+    /// Reads the top `bits` bits of a time-prefixed id as milliseconds since its epoch.
+    /// For [new_v7](Self::new_v7) ids, that's 44 bits; see also [system_time](Self::system_time).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is more than 64.
+    pub const fn duration_since_epoch(self, bits: u32) -> std::time::Duration {
+        assert!(bits <= 64, "bits must be at most 64");
+
+        // a shift by the full 64 bits means no timestamp at all
+        let millis = match self.0.checked_shr(64 - bits) {
+            Some(v) => v,
+            None => 0,
+        };
+
+        std::time::Duration::from_millis(millis)
+    }
+
+    /// Creates a new AssetId with the given Id.
+    pub const fn id(id: u64) -> Self {
+        Self(id)
+    }
+
+    /// Creates a random-looking id from a seed at compile time, for `const` ids:
+    /// `const IDS: [U64Id; 2] = [U64Id::new_const(0), U64Id::new_const(1)];`.
+    ///
+    /// This runs one step of [splitmix_next](Self::splitmix_next) over the seed, so it is
+    /// stable across versions and never `NULL`. Distinct seeds give distinct ids, with
+    /// one exception: the seed which would have produced `NULL` collides with seed `0`.
+    pub const fn new_const(seed: u64) -> Self {
+        Self(seed).splitmix_next()
+    }
+
+    /// Creates an id from a name, by hashing its bytes with 64-bit FNV-1a.
+    ///
+    /// The output is stable across versions of this crate and across platforms, so it
+    /// is safe to persist. In the vanishingly rare case the hash is `0`, it is remapped
+    /// to `1` so that this never returns `NULL`.
+    pub const fn from_name(name: &str) -> Self {
+        const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const PRIME: u64 = 0x100000001b3;
+
+        let bytes = name.as_bytes();
+        let mut hash = OFFSET_BASIS;
+        let mut i = 0;
+        while i < bytes.len() {
+            hash ^= bytes[i] as u64;
+            hash = hash.wrapping_mul(PRIME);
+            i += 1;
+        }
+
+        Self::non_null(hash)
+    }
+
+    /// Packs two ids into a `u128`, with `hi` in the top 64 bits and `lo` in the bottom,
+    /// for composite keys. Unpack with `(v >> 64) as u64` and `v as u64`.
+    pub const fn pack_u128(hi: U64Id, lo: U64Id) -> u128 {
+        ((hi.0 as u128) << 64) | lo.0 as u128
+    }
+
+    /// Derives a single id from two others, such as an id for a directed edge from its
+    /// two node ids. The order matters, so `combine(a, b)` and `combine(b, a)` differ.
+    ///
+    /// The output is stable across versions of this crate and is never `NULL`.
+    pub const fn combine(a: U64Id, b: U64Id) -> Self {
+        // mixing `a` before folding in `b` is what makes this order sensitive
+        Self::non_null(mix64(mix64(a.0) ^ b.0.rotate_left(32)))
+    }
+
+    /// Like [combine](Self::combine), but symmetric, so `combine_unordered(a, b)` and
+    /// `combine_unordered(b, a)` are the same. Use this for undirected edges.
+    pub const fn combine_unordered(a: U64Id, b: U64Id) -> Self {
+        if a.0 <= b.0 {
+            Self::combine(a, b)
+        } else {
+            Self::combine(b, a)
+        }
+    }
+
+    /// Maps a hash output of `0` to `1`, so that hashes are never `NULL`.
+    const fn non_null(hash: u64) -> Self {
+        if hash == 0 {
+            Self(1)
+        } else {
+            Self(hash)
+        }
+    }
+
+    /// Creates an id counting down from the top of the id space, so that
+    /// `from_top(0)` is `u64::MAX`, `from_top(1)` is the value below it, and so on.
+    ///
+    /// This keeps well-known sentinels readable and far away from counter-generated ids:
+    /// `const PENDING: U64Id = U64Id::from_top(1);`. Note that `NULL` lives at the bottom
+    /// of the space, not the top, so it is `from_top(u64::MAX)`.
+    pub const fn from_top(offset: u64) -> Self {
+        Self(u64::MAX - offset)
+    }
+
+    /// Checks if the asset is the `null` ID.
+    pub const fn is_null(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Converts an `Option` into the compact sentinel form, mapping `None` to `NULL`.
+    pub const fn from_option(opt: Option<U64Id>) -> Self {
+        match opt {
+            Some(id) => id,
+            None => Self::NULL,
+        }
+    }
+
+    /// Converts the id into an `Option`, mapping `NULL` to `None`.
+    pub const fn to_option(self) -> Option<U64Id> {
+        if self.is_null() {
+            None
+        } else {
+            Some(self)
+        }
+    }
+
+    /// Checks if the inner value is within `range`. Unlike `Range::contains`, this is `const`.
+    pub const fn is_in(self, range: core::ops::Range<u64>) -> bool {
+        range.start <= self.0 && self.0 < range.end
+    }
+
+    /// Returns the id if it is within `range`, and `None` otherwise. This composes
+    /// nicely with `?`: `id.checked_in(USER_RANGE)?`.
+    pub const fn checked_in(self, range: core::ops::Range<u64>) -> Option<Self> {
+        if self.is_in(range) {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the inner value.
+    pub const fn inner(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the raw value, for handing across an FFI boundary. Same as `inner`.
+    pub const fn into_raw(self) -> u64 {
+        self.0
+    }
+
+    /// Wraps a raw value from across an FFI boundary. No validation is done, so
+    /// this may be `NULL`. Same as `id`.
+    pub const fn from_raw(v: u64) -> Self {
+        Self(v)
+    }
+
+    /// Compares ids the way their `Display` strings sort as text. Since the hex
+    /// isn't padded, this disagrees with `Ord`: `*10` sorts before `*9`. Use this
+    /// when a backend sort has to match a frontend sorting the displayed strings.
+    pub fn cmp_by_display(self, other: U64Id) -> core::cmp::Ordering {
+        let mut a = [0; 16];
+        let mut b = [0; 16];
+        hex_str(self.0, &mut a).cmp(hex_str(other.0, &mut b))
+    }
+
+    /// Returns how far apart two ids are, regardless of which is larger. For
+    /// counter-allocated ids, this is how many were allocated between them.
+    pub const fn distance(self, other: U64Id) -> u64 {
+        self.0.abs_diff(other.0)
+    }
+
+    /// Returns the id `n` after this one, or `None` if that would overflow `u64`. Useful
+    /// as a cursor for paging through counter-allocated ids.
+    pub const fn nth_after(self, n: u64) -> Option<Self> {
+        match self.0.checked_add(n) {
+            Some(v) => Some(Self(v)),
+            None => None,
+        }
+    }
+
+    /// Rotates the bits of the id left by `n`. The result may be `NULL` only if the id was.
+    pub const fn rotate_left(self, n: u32) -> Self {
+        Self(self.0.rotate_left(n))
+    }
+
+    /// Rotates the bits of the id right by `n`. The result may be `NULL` only if the id was.
+    pub const fn rotate_right(self, n: u32) -> Self {
+        Self(self.0.rotate_right(n))
+    }
+
+    /// Reverses the order of the bits in the id. Sequential counter ids differ in their
+    /// low bits, so reversing them spreads neighbours far apart in a tree or hash ring.
+    pub const fn reverse_bits(self) -> Self {
+        Self(self.0.reverse_bits())
+    }
+
+    /// Converts the id into an index for a dense array, like a `Vec` of counter-allocated
+    /// ids. Returns `None` for `NULL`, or if the id doesn't fit in a `usize` (which can
+    /// happen on 32-bit targets).
+    pub fn as_index(self) -> Option<usize> {
+        if self.is_null() {
+            return None;
+        }
+
+        usize::try_from(self.0).ok()
+    }
+
+    /// Returns a reference to the inner value, for APIs which take a `&u64`.
+    pub const fn as_u64_ref(&self) -> &u64 {
+        &self.0
+    }
+
+    /// Returns the number of ones in the binary representation of the id.
+    pub const fn count_ones(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Returns the number of zeros in the binary representation of the id.
+    pub const fn count_zeros(self) -> u32 {
+        self.0.count_zeros()
+    }
+
+    /// Returns the number of leading zeros in the binary representation of the id.
+    pub const fn leading_zeros(self) -> u32 {
+        self.0.leading_zeros()
+    }
+
+    /// Reinterprets the id's bits as an `i64`, for databases without unsigned integers.
+    ///
+    /// All 64 bits are kept, so ids with the high bit set come out negative. Round-trip
+    /// with [from_i64_bits](Self::from_i64_bits).
+    pub const fn to_i64_bits(self) -> i64 {
+        self.0 as i64
+    }
+
+    /// Reinterprets the bits of an `i64` as an id. This is the inverse of
+    /// [to_i64_bits](Self::to_i64_bits), and negative values are not an error.
+    pub const fn from_i64_bits(v: i64) -> Self {
+        Self(v as u64)
+    }
+
+    /// Returns the id as big-endian bytes, suitable as a key in an ordered byte store.
+    ///
+    /// The lexicographic order of the returned bytes is guaranteed to match the
+    /// `Ord` of `U64Id`, so range scans over these keys visit ids in numeric order.
+    /// `NULL` sorts first.
+    pub const fn to_sortable_bytes(self) -> [u8; 8] {
+        self.0.to_be_bytes()
+    }
+
+    /// Writes the id as lowercase hex, in the serde form, straight to `w` with no allocation.
+    pub fn write_hex<W: std::io::Write>(self, w: &mut W) -> std::io::Result<()> {
+        let mut buf = [0; 16];
+        w.write_all(hex_str(self.0, &mut buf).as_bytes())
+    }
+
+    /// Writes the id as its 8 big-endian bytes straight to `w`.
+    pub fn write_bytes_be<W: std::io::Write>(self, w: &mut W) -> std::io::Result<()> {
+        w.write_all(&self.0.to_be_bytes())
+    }
+
+    /// Encodes the id as lowercase hex followed by a Luhn mod 16 check character, for ids
+    /// which people type by hand.
+    ///
+    /// The check character catches every single mistyped character, and every swap of two
+    /// adjacent characters except `0` and `f`. Decode with
+    /// [from_checked_string](Self::from_checked_string).
+    pub fn to_checked_string(self) -> String {
+        let mut buf = [0; 16];
+        let hex = hex_str(self.0, &mut buf);
+
+        let check = luhn_mod_16(hex.bytes().rev().map(hex_digit_value), 2);
+        let check = HEX_DIGITS[((16 - check) % 16) as usize] as char;
+
+        let mut output = String::with_capacity(hex.len() + 1);
+        output.push_str(hex);
+        output.push(check);
+
+        output
+    }
+
+    /// Decodes an id from [to_checked_string](Self::to_checked_string), accepting either case.
+    ///
+    /// A string which isn't hex, or is too short or long, is an error about its shape; a
+    /// well-formed string with the wrong check character is [U64IdError::Checksum].
+    pub fn from_checked_string(s: &str) -> Result<Self, U64IdError> {
+        if s.len() < 2 || s.len() > 17 {
+            return Err(U64IdError::InvalidLength(s.len()));
+        }
+        if let Some(c) = s.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(U64IdError::InvalidChar(c));
+        }
+
+        if luhn_mod_16(s.bytes().rev().map(hex_digit_value), 1) != 0 {
+            return Err(U64IdError::Checksum);
+        }
+
+        u64::from_str_radix(&s[..s.len() - 1], 16)
+            .map(Self)
+            .map_err(U64IdError::Parse)
+    }
+
+    /// Parses an id from whatever form a user pasted in, trying each of these in order
+    /// and returning the first success:
+    ///
+    /// 1. hex with a `0x` prefix, or with the `*` prefix used by `Display`
+    /// 2. decimal, if every character is a digit
+    /// 3. base58
+    /// 4. bare hex
+    ///
+    /// The order matters, since many strings are valid in several encodings: `"255"`
+    /// parses as decimal 255, not hex `0x255`, and `"ff"` parses as base58. If you know
+    /// which form you have, use the specific parser instead.
+    pub fn parse_flexible(s: &str) -> Result<Self, U64IdError> {
+        if let Some(hex) = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .or_else(|| s.strip_prefix('*'))
+        {
+            return parse_hex(hex);
+        }
+
+        if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+            if let Ok(v) = s.parse() {
+                return Ok(Self(v));
+            }
+        }
+
+        Self::from_base58(s).or_else(|_| parse_hex(s))
+    }
+
+    /// Cheaply checks if `s` looks like a serialized id: 1 to 16 hex digits, in either case.
+    /// Anything that passes this check fits in a `u64` and will deserialize.
+    pub const fn is_valid_hex_id(s: &str) -> bool {
+        let bytes = s.as_bytes();
+        if bytes.is_empty() || bytes.len() > 16 {
+            return false;
+        }
+
+        let mut i = 0;
+        while i < bytes.len() {
+            if !bytes[i].is_ascii_hexdigit() {
+                return false;
+            }
+            i += 1;
+        }
+
+        true
+    }
+
+    /// Formats the id in any radix from 2 to 36, using lowercase `0-9a-z` digits and
+    /// no prefix. This is the inverse of `u64::from_str_radix`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radix` is outside `2..=36`.
+    pub fn to_string_radix(self, radix: u32) -> String {
+        assert!(
+            (2..=36).contains(&radix),
+            "radix must be in 2..=36, got {radix}"
+        );
+
+        // base 2 is the widest, at one digit per bit
+        let mut buf = [0u8; 64];
+        let mut start = buf.len();
+        let mut val = self.0;
+        loop {
+            start -= 1;
+            buf[start] = char::from_digit((val % radix as u64) as u32, radix).unwrap() as u8;
+            val /= radix as u64;
+            if val == 0 {
+                break;
+            }
+        }
+
+        buf[start..].iter().map(|&b| b as char).collect()
+    }
+
+    /// Encodes the id as base58, using the Bitcoin alphabet.
+    pub fn to_base58(self) -> String {
+        if self.0 == 0 {
+            return (BASE58_ALPHABET[0] as char).to_string();
+        }
+
+        let mut buf = [0u8; Self::BASE58_MAX_LEN];
+        let mut start = buf.len();
+        let mut val = self.0;
+        while val != 0 {
+            start -= 1;
+            buf[start] = BASE58_ALPHABET[(val % 58) as usize];
+            val /= 58;
+        }
+
+        // the alphabet is all ascii, so each byte is its own char
+        buf[start..].iter().map(|&b| b as char).collect()
+    }
+
+    /// Decodes an id from base58, using the Bitcoin alphabet.
+    ///
+    /// This errors on any character outside the alphabet, and on values larger than `u64::MAX`.
+    pub fn from_base58(s: &str) -> Result<Self, U64IdError> {
+        if s.is_empty() {
+            return Err(U64IdError::InvalidLength(0));
+        }
+
+        let mut val: u64 = 0;
+        for c in s.chars() {
+            let digit = BASE58_ALPHABET
+                .iter()
+                .position(|&b| b as char == c)
+                .ok_or(U64IdError::InvalidChar(c))?;
+
+            val = val
+                .checked_mul(58)
+                .and_then(|v| v.checked_add(digit as u64))
+                .ok_or(U64IdError::Overflow)?;
+        }
+
+        Ok(Self(val))
+    }
+
+    /// Returns whichever of plain hex and base58 is shorter, preferring hex on a tie.
+    /// base32 is never shorter than base58, so it's not a candidate.
+    ///
+    /// base58 is never longer than hex, so this is base58 unless the two tie, which only
+    /// happens for ids below 4096. The output doesn't say which encoding it used, so it's
+    /// for display only. Store or parse ids with a specific encoding instead.
+    pub fn to_shortest(self) -> String {
+        let hex = String::from(self);
+        let base58 = self.to_base58();
+        if base58.len() < hex.len() {
+            base58
+        } else {
+            hex
+        }
+    }
+
+    /// Encodes the id's 8 big-endian bytes as RFC 4648 base32, without padding.
+    ///
+    /// The output is always 13 uppercase characters. This is the standard alphabet, not
+    /// base32hex or Crockford's variant.
+    pub fn to_base32(self) -> String {
+        // 64 bits doesn't divide into 5 bit chunks, so the final chunk gets a zero pad bit
+        let bits = (self.0 as u128) << 1;
+
+        (0..Self::BASE32_LEN)
+            .map(|i| BASE32_ALPHABET[((bits >> (60 - 5 * i)) & 0x1f) as usize] as char)
+            .collect()
+    }
+
+    /// Decodes an id from RFC 4648 base32, accepting either case.
+    ///
+    /// This errors unless there are exactly 13 characters from the alphabet, and on
+    /// strings which encode more than 64 bits.
+    pub fn from_base32(s: &str) -> Result<Self, U64IdError> {
+        if s.len() != Self::BASE32_LEN {
+            return Err(U64IdError::InvalidLength(s.len()));
+        }
+
+        let mut bits: u128 = 0;
+        for c in s.chars() {
+            let digit = BASE32_ALPHABET
+                .iter()
+                .position(|&b| b as char == c.to_ascii_uppercase())
+                .ok_or(U64IdError::InvalidChar(c))?;
+
+            bits = (bits << 5) | digit as u128;
+        }
+
+        if bits & 1 != 0 {
+            return Err(U64IdError::Overflow);
+        }
+
+        Ok(Self((bits >> 1) as u64))
+    }
+
+    /// Encodes the id as four pronounceable words, like `lusab-babad-gutih-tugad`,
+    /// which are much easier to compare by eye across logs than hex.
+    ///
+    /// Each word is a proquint encoding sixteen bits, so no wordlist is needed and every id
+    /// round-trips through [from_mnemonic](Self::from_mnemonic). This is meant for
+    /// debugging; store ids in one of the compact forms instead.
+    pub fn to_mnemonic(self) -> String {
+        let mut output = String::with_capacity(23);
+        for i in (0..4).rev() {
+            let chunk = (self.0 >> (i * 16)) as u16;
+
+            output.push(PROQUINT_CONSONANTS[(chunk >> 12) as usize & 0xf] as char);
+            output.push(PROQUINT_VOWELS[(chunk >> 10) as usize & 0x3] as char);
+            output.push(PROQUINT_CONSONANTS[(chunk >> 6) as usize & 0xf] as char);
+            output.push(PROQUINT_VOWELS[(chunk >> 4) as usize & 0x3] as char);
+            output.push(PROQUINT_CONSONANTS[chunk as usize & 0xf] as char);
+
+            if i != 0 {
+                output.push('-');
+            }
+        }
+
+        output
+    }
+
+    /// Decodes an id from the words produced by [to_mnemonic](Self::to_mnemonic).
+    pub fn from_mnemonic(s: &str) -> Result<Self, U64IdError> {
+        let words: Vec<&str> = s.split('-').collect();
+        if words.len() != 4 || words.iter().any(|w| w.len() != 5) {
+            return Err(U64IdError::InvalidLength(s.len()));
+        }
+
+        let mut val = 0;
+        for word in words {
+            for (i, c) in word.chars().enumerate() {
+                let (alphabet, bits): (&[u8], u32) = if i % 2 == 0 {
+                    (PROQUINT_CONSONANTS, 4)
+                } else {
+                    (PROQUINT_VOWELS, 2)
+                };
+
+                let digit = alphabet
+                    .iter()
+                    .position(|&b| b as char == c)
+                    .ok_or(U64IdError::InvalidChar(c))?;
+
+                val = (val << bits) | digit as u64;
+            }
+        }
+
+        Ok(Self(val))
+    }
+
+    /// Picks which of `n` shards this id belongs to, returning a value in `0..n`.
+    ///
+    /// This uses a multiply-high reduction rather than `%`, which is faster and just as
+    /// uniform for random ids. It uses the high bits of the id, so for counter-generated
+    /// ids, consider sharding on [ring_position](Self::ring_position) instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub const fn shard(self, n: u32) -> u32 {
+        assert!(n != 0, "cannot shard into zero shards");
+
+        multiply_high(self.0, n as u64) as u32
+    }
+
+    /// Returns this id's position on a 64-bit consistent-hashing ring.
+    ///
+    /// The raw value is run through a fixed finalizer so that adjacent ids scatter
+    /// across the ring. This output is stable across versions of this crate, so
+    /// positions can be persisted. Place your nodes on the same ring and route
+    /// each id to the first node at or after its position.
+    pub const fn ring_position(self) -> u64 {
+        mix64(self.0)
+    }
+
+    /// Narrows the id to 32 bits, for tightly-packed tables.
+    ///
+    /// Rather than cutting off the high bits, which would make sequential ids collide in
+    /// predictable patterns, the id is mixed first so every input bit affects the result.
+    /// The output is stable across versions of this crate.
+    pub const fn to_u32_mixed(self) -> u32 {
+        (mix64(self.0) >> 32) as u32
+    }
+
+    /// Treats this id as a SplitMix64-style state and returns the next id in its stream.
+    ///
+    /// This needs no rng at all: start from any seed id and call it repeatedly to get a
+    /// well-distributed, deterministic sequence. The step is a permutation of the id space,
+    /// so the sequence does not fall into short cycles. `NULL` is skipped, so this never
+    /// returns it. This is not cryptographically secure, and the sequence is stable
+    /// across versions of this crate.
+    pub const fn splitmix_next(self) -> Self {
+        const GOLDEN_GAMMA: u64 = 0x9e3779b97f4a7c15;
+
+        let mut next = mix64(self.0.wrapping_add(GOLDEN_GAMMA));
+        if next == 0 {
+            next = mix64(next.wrapping_add(GOLDEN_GAMMA));
+        }
+
+        Self(next)
+    }
+
+    /// Scrambles the id with a keyed, reversible permutation, so sequential ids can be
+    /// published without revealing how many came before them.
+    ///
+    /// This is a small Feistel network, and [deobfuscate](Self::deobfuscate) with the same
+    /// key undoes it. It hides the obvious patterns, but it is not encryption: don't rely on
+    /// it to keep ids secret from a determined attacker. Since it's a permutation, exactly
+    /// one input per key maps to `NULL`.
+    pub const fn obfuscate(self, key: u64) -> Self {
+        let mut left = (self.0 >> 32) as u32;
+        let mut right = self.0 as u32;
+
+        let mut round = 0;
+        while round < FEISTEL_ROUNDS {
+            let next = left ^ feistel_round(right, key, round);
+            left = right;
+            right = next;
+            round += 1;
+        }
+
+        Self(((left as u64) << 32) | right as u64)
+    }
+
+    /// Reverses [obfuscate](Self::obfuscate), given the same key.
+    pub const fn deobfuscate(self, key: u64) -> Self {
+        let mut left = (self.0 >> 32) as u32;
+        let mut right = self.0 as u32;
+
+        let mut round = FEISTEL_ROUNDS;
+        while round > 0 {
+            round -= 1;
+            let prev = right ^ feistel_round(left, key, round);
+            right = left;
+            left = prev;
+        }
+
+        Self(((left as u64) << 32) | right as u64)
+    }
+
+    /// Creates an id from 2D coordinates using Morton (Z-order) encoding.
+    ///
+    /// The bits of `x` and `y` are interleaved, with `x` in the even bits, so cells
+    /// that are near each other in space tend to get numerically near ids.
+    pub const fn from_morton(x: u32, y: u32) -> Self {
+        Self(spread_bits_32(x) | (spread_bits_32(y) << 1))
+    }
+
+    /// Decodes the 2D coordinates from an id made with [from_morton](Self::from_morton).
+    pub const fn to_morton(self) -> (u32, u32) {
+        (compact_bits(self.0), compact_bits(self.0 >> 1))
+    }
+
+    /// Returns a `tracing` value which records this id as its lowercase hex form,
+    /// matching serde.
+    ///
+    /// `tracing::Value` is sealed, so `U64Id` can't implement it directly. Use this
+    /// instead: `tracing::info!(id = id.as_value(), "loaded")`.
+    #[cfg(feature = "tracing")]
+    pub fn as_value(self) -> impl tracing::Value {
+        struct Hex(u64);
+        impl fmt::Display for Hex {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{:x}", self.0)
+            }
+        }
+
+        tracing::field::display(Hex(self.0))
+    }
+}
+
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+pub use tracing as __tracing;
+
+/// Emits a `tracing` event which records an id twice, as `id.hex` and `id.num`, so
+/// logs can be searched by either form. This takes a level and an id, followed by
+/// whatever else you'd pass to `tracing::event!`:
+/// `u64_id::event_with_id!(Level::INFO, id, user = name, "loaded asset")`.
+#[cfg(feature = "tracing")]
+#[macro_export]
+macro_rules! event_with_id {
+    ($lvl:expr, $id:expr, $($arg:tt)+) => {{
+        let id: $crate::U64Id = $id;
+        $crate::__tracing::event!($lvl, id.hex = id.as_value(), id.num = id.inner(), $($arg)+)
+    }};
+}
+
+/// The number of random bits at the bottom of a [U64Id::new_v7] id.
+const V7_RANDOM_BITS: u32 = 20;
+
+/// Maps `val` uniformly onto `0..n`, by taking the high half of `val * n`.
+const fn multiply_high(val: u64, n: u64) -> u64 {
+    ((val as u128 * n as u128) >> 64) as u64
+}
+
+/// The SplitMix64 finalizer. It's a bijection, and small changes in the input
+/// change about half the output bits. Its output is relied upon to be stable.
+const fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+const FEISTEL_ROUNDS: u32 = 4;
+
+/// The round function for [U64Id::obfuscate]. Each round gets its own slice of the key.
+const fn feistel_round(half: u32, key: u64, round: u32) -> u32 {
+    (mix64(half as u64 ^ key.rotate_left(round * 16) ^ round as u64) >> 32) as u32
+}
+
+/// Spreads the bits of `x` out so that there is a zero between each of them, putting
+/// bit `i` of `x` at bit `2 * i` of the output. `0b11` becomes `0b0101`.
+///
+/// This is the building block of [U64Id::from_morton], exposed for custom interleavings.
+pub const fn spread_bits_32(x: u32) -> u64 {
+    let mut x = x as u64;
+    x = (x | (x << 16)) & 0x0000_ffff_0000_ffff;
+    x = (x | (x << 8)) & 0x00ff_00ff_00ff_00ff;
+    x = (x | (x << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    x = (x | (x << 2)) & 0x3333_3333_3333_3333;
+    (x | (x << 1)) & 0x5555_5555_5555_5555
+}
+
+/// The inverse of [spread_bits_32], gathering the even bits of `x` back together.
+/// Odd bits are ignored.
+pub const fn compact_bits(x: u64) -> u32 {
+    let mut x = x & 0x5555_5555_5555_5555;
+    x = (x | (x >> 1)) & 0x3333_3333_3333_3333;
+    x = (x | (x >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+    x = (x | (x >> 4)) & 0x00ff_00ff_00ff_00ff;
+    x = (x | (x >> 8)) & 0x0000_ffff_0000_ffff;
+    (x | (x >> 16)) as u32
+}
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Writes `val` as minimal-width lowercase hex into `buf`, exactly like `{:x}`,
+/// and returns the written digits. This lets us format without allocating.
+fn hex_str(val: u64, buf: &mut [u8; 16]) -> &str {
+    let len = (16 - val.leading_zeros() as usize / 4).max(1);
+    for (i, b) in buf[..len].iter_mut().rev().enumerate() {
+        *b = HEX_DIGITS[((val >> (i * 4)) & 0xf) as usize];
+    }
+
+    // every byte we wrote is an ascii hex digit
+    core::str::from_utf8(&buf[..len]).unwrap()
+}
+
+/// Writes `val` as exactly 16 lowercase hex digits into `buf`, zero padded.
+#[cfg(feature = "serde-padded")]
+fn padded_hex_str(val: u64, buf: &mut [u8; 16]) -> &str {
+    for (i, b) in buf.iter_mut().rev().enumerate() {
+        *b = HEX_DIGITS[((val >> (i * 4)) & 0xf) as usize];
+    }
+
+    // every byte we wrote is an ascii hex digit
+    core::str::from_utf8(buf).unwrap()
+}
+
+/// Parses bare hex, of either case, into an id.
+fn parse_hex(s: &str) -> Result<U64Id, U64IdError> {
+    u64::from_str_radix(s, 16)
+        .map(U64Id)
+        .map_err(|e| match e.kind() {
+            core::num::IntErrorKind::PosOverflow => U64IdError::Overflow,
+            _ => U64IdError::Parse(e),
+        })
+}
+
+/// Returns the value of an ascii hex digit, of either case. Anything else is garbage.
+fn hex_digit_value(b: u8) -> u64 {
+    (b as char).to_digit(16).unwrap_or_default() as u64
+}
+
+/// Sums digits, right to left, for the Luhn mod 16 algorithm. Every other digit is
+/// doubled, starting with `first_factor`. Returns the sum mod 16.
+fn luhn_mod_16(digits_from_right: impl Iterator<Item = u64>, first_factor: u64) -> u64 {
+    let mut factor = first_factor;
+    let mut sum = 0;
+    for digit in digits_from_right {
+        let addend = digit * factor;
+        sum += addend / 16 + addend % 16;
+        factor = if factor == 2 { 1 } else { 2 };
+    }
+
+    sum % 16
+}
+
+const PROQUINT_CONSONANTS: &[u8; 16] = b"bdfghjklmnprstvz";
+const PROQUINT_VOWELS: &[u8; 4] = b"aiou";
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// The error for every fallible operation in this crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum U64IdError {
+    /// The input was not a valid integer.
+    Parse(core::num::ParseIntError),
+    /// The value was outside of the range it needed to be in.
+    OutOfRange,
+    /// The input had the wrong length. This holds the length that was found.
+    InvalidLength(usize),
+    /// The input was a valid encoding of a value larger than `u64::MAX`.
+    Overflow,
+    /// The input contained a character outside of the encoding's alphabet.
+    InvalidChar(char),
+    /// The input was well-formed, but its check character didn't match, so it was
+    /// probably mistyped.
+    Checksum,
+}
+
+impl fmt::Display for U64IdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // the ParseIntError itself is exposed through `source`
+            Self::Parse(_) => f.write_str("could not parse id as an integer"),
+            Self::OutOfRange => f.write_str("id is out of range"),
+            Self::InvalidLength(len) => write!(f, "invalid length {} for an id", len),
+            Self::Overflow => f.write_str("id is too large to fit in a u64"),
+            Self::InvalidChar(c) => write!(f, "invalid character {:?} in id", c),
+            Self::Checksum => f.write_str("id checksum does not match"),
+        }
+    }
+}
+
+impl std::error::Error for U64IdError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Parse(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// A `HashMap` keyed by [U64Id], hashed with `FxHasher`.
+///
+/// Fx is much faster than the default SipHash, and unlike an identity hasher
+/// it still mixes its input, so it stays well-behaved for counter-generated or
+/// otherwise non-uniform ids. It is not resistant to deliberate HashDoS.
+#[cfg(feature = "fxhash")]
+pub type U64IdFxMap<V> = std::collections::HashMap<U64Id, V, rustc_hash::FxBuildHasher>;
+
+/// A `HashSet` of [U64Id], hashed with `FxHasher`. See [U64IdFxMap] for the tradeoffs.
+#[cfg(feature = "fxhash")]
+pub type U64IdFxSet = std::collections::HashSet<U64Id, rustc_hash::FxBuildHasher>;
+
+/// A sequential id generator which can be shared between threads or tasks
+/// without any external locking.
+///
+/// It is a single atomic counter, so it is lock-free, but every call to [next](Self::next)
+/// contends on the same cache line. That is fine for most services; if it shows up
+/// in a profile, have each worker reserve ids in bulk instead.
+///
+/// Ids start at `1`, so this never produces `NULL`.
+#[derive(Debug)]
+pub struct SharedU64IdGen {
+    next: std::sync::atomic::AtomicU64,
+}
+
+impl SharedU64IdGen {
+    /// Creates a new generator, which will start at `1`.
+    pub const fn new() -> Self {
+        Self {
+            next: std::sync::atomic::AtomicU64::new(1),
+        }
+    }
+
+    /// Returns the next id.
+    ///
+    /// # Panics
+    ///
+    /// Panics once the counter reaches `u64::MAX`.
+    pub fn next(&self) -> U64Id {
+        use std::sync::atomic::Ordering;
+
+        self.next
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| v.checked_add(1))
+            .map(U64Id)
+            .expect("SharedU64IdGen ran out of ids")
+    }
+
+    /// Reserves `size` contiguous ids at once, so a client can mint them locally
+    /// without touching the shared counter again.
+    ///
+    /// Returns `None`, and reserves nothing, if there isn't enough space left.
+    pub fn allocate_block(&self, size: u64) -> Option<Block> {
+        use std::sync::atomic::Ordering;
+
+        self.next
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+                v.checked_add(size)
+            })
+            .ok()
+            .map(|start| Block {
+                start: U64Id(start),
+                len: size,
+            })
+    }
+}
+
+/// A contiguous run of ids, reserved with [SharedU64IdGen::allocate_block].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Block {
+    /// The first id in the block.
+    pub start: U64Id,
+    /// The number of ids in the block.
+    pub len: u64,
+}
 
-    /// Creates a new, random AssetId, seeded cheaply from thread_rng.
+impl Block {
+    /// Iterates over every id in the block, in order.
+    pub fn iter(&self) -> impl Iterator<Item = U64Id> {
+        (self.start.0..self.start.0 + self.len).map(U64Id)
+    }
+
+    /// Checks if the given id is within this block.
+    pub const fn contains(&self, id: U64Id) -> bool {
+        self.start.0 <= id.0 && id.0 - self.start.0 < self.len
+    }
+}
+
+/// Returns the smallest id, skipping any `NULL`s. Returns `None` if there are no
+/// non-null ids.
+pub fn min_ignoring_null(ids: impl IntoIterator<Item = U64Id>) -> Option<U64Id> {
+    ids.into_iter().filter(|v| !v.is_null()).min()
+}
+
+/// Returns the largest id, skipping any `NULL`s. Returns `None` if there are no
+/// non-null ids.
+pub fn max_ignoring_null(ids: impl IntoIterator<Item = U64Id>) -> Option<U64Id> {
+    ids.into_iter().filter(|v| !v.is_null()).max()
+}
+
+/// Generates `samples` random ids and counts how many land in each of `buckets`
+/// buckets, using the same reduction as [U64Id::shard]. With enough samples, every
+/// count should be close to `samples / buckets`.
+///
+/// # Panics
+///
+/// Panics if `buckets` is zero.
+#[cfg(feature = "rand")]
+pub fn histogram<R: rand::Rng + ?Sized>(rng: &mut R, samples: usize, buckets: usize) -> Vec<usize> {
+    assert!(buckets != 0, "cannot make a histogram with zero buckets");
+
+    let mut counts = vec![0; buckets];
+    for _ in 0..samples {
+        let id = U64Id::from_rng(rng);
+        counts[multiply_high(id.0, buckets as u64) as usize] += 1;
+    }
+
+    counts
+}
+
+/// Generates `n` distinct random ids, sorted in ascending order. None of them are `NULL`.
+#[cfg(feature = "rand")]
+pub fn generate_n_unique_sorted<R: rand::Rng + ?Sized>(rng: &mut R, n: usize) -> Vec<U64Id> {
+    // a btree set dedups and sorts in one go
+    let mut ids = std::collections::BTreeSet::new();
+    while ids.len() < n {
+        ids.insert(U64Id::from_rng(rng));
+    }
+
+    ids.into_iter().collect()
+}
+
+/// Draws `k` distinct ids from `range`, in random order, like dealing cards from a
+/// small deck. Unlike [generate_n_unique_sorted], this never rejection samples, so it
+/// stays quick even when `k` is the whole range.
+///
+/// This errors with [U64IdError::OutOfRange] if `k` is more than the range holds. If
+/// the range includes 0, `NULL` may be drawn.
+#[cfg(feature = "rand")]
+pub fn sample_unique_in<R: rand::Rng + ?Sized>(
+    rng: &mut R,
+    range: core::ops::Range<u64>,
+    k: usize,
+) -> Result<Vec<U64Id>, U64IdError> {
+    let len = range.end.saturating_sub(range.start);
+    if k as u64 > len {
+        return Err(U64IdError::OutOfRange);
+    }
+
+    // a partial Fisher-Yates shuffle, storing only the slots which have been swapped,
+    // so this is O(k) no matter how large the range is
+    let mut swapped = std::collections::HashMap::with_capacity(k);
+    let mut output = Vec::with_capacity(k);
+    for i in 0..k as u64 {
+        let j = rng.gen_range(i..len);
+        let picked = swapped.get(&j).copied().unwrap_or(j);
+        let displaced = swapped.get(&i).copied().unwrap_or(i);
+        swapped.insert(j, displaced);
+        output.push(U64Id(range.start + picked));
+    }
+
+    Ok(output)
+}
+
+/// Finds the first id which appears twice in `ids`, returning the indices of its first
+/// and second appearances. Returns `None` if every id is unique. This is O(n).
+pub fn find_duplicate(ids: &[U64Id]) -> Option<(usize, usize)> {
+    let mut seen = std::collections::HashMap::with_capacity(ids.len());
+    for (i, id) in ids.iter().enumerate() {
+        if let Some(&first) = seen.get(id) {
+            return Some((first, i));
+        }
+        seen.insert(*id, i);
+    }
+
+    None
+}
+
+/// Looks up `id` in `map`, returning `None` straight away if it's `NULL`, since `NULL`
+/// is never a real key. This works with any hasher, including [U64IdFxMap].
+pub fn get_non_null<V, S: core::hash::BuildHasher>(
+    map: &std::collections::HashMap<U64Id, V, S>,
+    id: U64Id,
+) -> Option<&V> {
+    if id.is_null() {
+        return None;
+    }
+
+    map.get(&id)
+}
+
+/// Checks if every id in `ids` is greater than the one before it, as counter and
+/// time-based generators should produce. Empty and single id slices count.
+pub fn is_strictly_increasing(ids: &[U64Id]) -> bool {
+    ids.windows(2).all(|w| w[0] < w[1])
+}
+
+/// Like [is_strictly_increasing], but allows repeats of the same id in a row.
+pub fn is_non_decreasing(ids: &[U64Id]) -> bool {
+    ids.windows(2).all(|w| w[0] <= w[1])
+}
+
+/// Groups `ids` into `n` buckets by [U64Id::shard], in one pass. Bucket `i` holds the
+/// ids with `shard(n) == i`, in the same order they were given.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+pub fn partition_by_shard(ids: &[U64Id], n: u32) -> Vec<Vec<U64Id>> {
+    assert!(n != 0, "cannot shard into zero shards");
+
+    let mut buckets = vec![Vec::new(); n as usize];
+    for &id in ids {
+        buckets[id.shard(n) as usize].push(id);
+    }
+
+    buckets
+}
+
+/// Removes every `NULL` id from `ids`, in place. `NULL` is the only invalid id.
+pub fn retain_valid(ids: &mut Vec<U64Id>) {
+    ids.retain(|v| !v.is_null());
+}
+
+/// Splits `ids` into the valid ids and the invalid (`NULL`) ids, preserving order.
+pub fn partition_valid(ids: Vec<U64Id>) -> (Vec<U64Id>, Vec<U64Id>) {
+    ids.into_iter().partition(|v| !v.is_null())
+}
+
+/// The result of [audit]ing a batch of ids. Every id falls into exactly one of
+/// `valid`, `null`, or `duplicate`, so they sum to the number of ids audited.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AuditReport {
+    /// The number of non-null ids seen for the first time.
+    pub valid: usize,
+    /// The number of `NULL` ids.
+    pub null: usize,
+    /// The number of non-null ids which had already appeared earlier in the batch.
+    pub duplicate: usize,
+    /// The indices of the first few `NULL` or duplicate ids, in order, up to
+    /// [MAX_PROBLEMS](Self::MAX_PROBLEMS) of them.
+    pub problems: Vec<usize>,
+}
+
+impl AuditReport {
+    /// The most problem indices a report keeps.
+    pub const MAX_PROBLEMS: usize = 16;
+
+    /// Checks if the batch had no `NULL` or duplicate ids.
+    pub fn is_clean(&self) -> bool {
+        self.null == 0 && self.duplicate == 0
+    }
+}
+
+/// Checks a batch of ids in one pass, such as a dump being imported, counting the
+/// valid, `NULL`, and duplicate ids and noting where the first problems are.
+///
+/// There's no reserved range of ids in this crate, so `NULL` is the only invalid value.
+pub fn audit(ids: &[U64Id]) -> AuditReport {
+    let mut report = AuditReport::default();
+    let mut seen = std::collections::HashSet::with_capacity(ids.len());
+    for (i, &id) in ids.iter().enumerate() {
+        if id.is_null() {
+            report.null += 1;
+        } else if !seen.insert(id) {
+            report.duplicate += 1;
+        } else {
+            report.valid += 1;
+            continue;
+        }
+
+        if report.problems.len() < AuditReport::MAX_PROBLEMS {
+            report.problems.push(i);
+        }
+    }
+
+    report
+}
+
+/// Assembles an id out of several bit-packed fields, like a version, a type tag,
+/// a shard, and a sequence number.
+///
+/// Every method is `const`, so a bad layout in a `const` fails at compile time. For example,
+/// `U64IdBuilder::new().field(60, 4, version).field(0, 60, sequence).build()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct U64IdBuilder {
+    value: u64,
+    used: u64,
+}
+
+impl U64IdBuilder {
+    /// Creates a builder with no fields set.
+    pub const fn new() -> Self {
+        Self { value: 0, used: 0 }
+    }
+
+    /// Places `value` in the `width_bits` bits starting at bit `offset_bits`, counting
+    /// from the least significant bit.
     ///
-    /// To avoid calling this internal function repeatedly, consider using [id]
-    /// and directly constructing your own rng handler.
+    /// # Panics
     ///
-    /// Internally, we use a u64 for random numbers. These have been, generally,
-    /// large enough.
-    #[cfg(feature = "rand")]
+    /// Panics if the field is empty, runs past bit 63, overlaps a field which was already
+    /// set, or if `value` doesn't fit in `width_bits` bits.
+    pub const fn field(self, offset_bits: u32, width_bits: u32, value: u64) -> Self {
+        assert!(width_bits != 0, "fields must be at least one bit wide");
+        assert!(
+            offset_bits < 64 && width_bits <= 64 - offset_bits,
+            "field does not fit in 64 bits"
+        );
+
+        let mask = if width_bits == 64 {
+            u64::MAX
+        } else {
+            (1 << width_bits) - 1
+        };
+        assert!(value & !mask == 0, "value is too large for its field");
+        assert!(
+            self.used & (mask << offset_bits) == 0,
+            "field overlaps an earlier field"
+        );
+
+        Self {
+            value: self.value | (value << offset_bits),
+            used: self.used | (mask << offset_bits),
+        }
+    }
+
+    /// Returns the assembled id. Bits not covered by any field are zero.
+    pub const fn build(self) -> U64Id {
+        U64Id(self.value)
+    }
+}
+
+/// An optional id which sorts `None` after every real id, unlike `Option`, which sorts
+/// `None` first. Wrap your ids in this and a plain `sort` does the right thing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SortableOptId(pub Option<U64Id>);
+
+impl PartialOrd for SortableOptId {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SortableOptId {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        use core::cmp::Ordering;
+
+        match (self.0, other.0) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
+
+/// How [sort_ids] should order a list of ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    /// Numeric order, the same as `Ord`.
+    Natural,
+    /// The numeric order of each id after [U64Id::obfuscate] with the given key, so
+    /// the order doesn't reveal anything about the underlying ids.
+    Obfuscated(u64),
+}
+
+/// Sorts `ids` in place according to `order`.
+pub fn sort_ids(ids: &mut [U64Id], order: SortOrder) {
+    match order {
+        SortOrder::Natural => ids.sort_unstable(),
+        SortOrder::Obfuscated(key) => ids.sort_unstable_by_key(|v| v.obfuscate(key)),
+    }
+}
+
+/// Merges already sorted `streams`, such as the ids made by several nodes, into one
+/// sorted `Vec`, leaving each stream empty. Duplicates are kept. This runs in
+/// O(n log k) for `k` streams.
+pub fn merge_sorted(streams: &mut [Vec<U64Id>]) -> Vec<U64Id> {
+    use core::cmp::Reverse;
+
+    let mut output = Vec::with_capacity(streams.iter().map(Vec::len).sum());
+    let mut drains: Vec<_> = streams.iter_mut().map(|v| v.drain(..)).collect();
+
+    // a min-heap holding the next id from each stream
+    let mut heap = std::collections::BinaryHeap::with_capacity(drains.len());
+    for (i, drain) in drains.iter_mut().enumerate() {
+        if let Some(id) = drain.next() {
+            heap.push(Reverse((id, i)));
+        }
+    }
+
+    while let Some(Reverse((id, i))) = heap.pop() {
+        output.push(id);
+        if let Some(next) = drains[i].next() {
+            heap.push(Reverse((next, i)));
+        }
+    }
+
+    output
+}
+
+/// A map keyed by [U64Id] which iterates in insertion order, for deterministic
+/// output (like snapshot tests). Lookups by id are O(1).
+#[derive(Debug, Clone)]
+pub struct OrderedU64IdMap<V> {
+    entries: Vec<(U64Id, V)>,
+    indices: std::collections::HashMap<U64Id, usize>,
+}
+
+impl<V> OrderedU64IdMap<V> {
+    /// Creates an empty map.
     pub fn new() -> Self {
-        use rand::Rng;
+        Self {
+            entries: Vec::new(),
+            indices: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Inserts a value, returning the old value if the id was already present.
+    /// Replacing a value keeps the id's original position.
+    pub fn insert(&mut self, id: U64Id, value: V) -> Option<V> {
+        match self.indices.get(&id) {
+            Some(&index) => Some(core::mem::replace(&mut self.entries[index].1, value)),
+            None => {
+                self.indices.insert(id, self.entries.len());
+                self.entries.push((id, value));
+                None
+            }
+        }
+    }
 
-        Self(rand::thread_rng().gen())
+    /// Returns a reference to the value for the given id.
+    pub fn get(&self, id: U64Id) -> Option<&V> {
+        self.indices.get(&id).map(|&index| &self.entries[index].1)
     }
 
-    /// Creates a new AssetId with the given Id.
-    pub const fn id(id: u64) -> Self {
-        Self(id)
+    /// Returns a mutable reference to the value for the given id.
+    pub fn get_mut(&mut self, id: U64Id) -> Option<&mut V> {
+        self.indices
+            .get(&id)
+            .map(|&index| &mut self.entries[index].1)
     }
 
-    /// Checks if the asset is the `null` ID.
-    pub const fn is_null(self) -> bool {
-        self.0 == 0
+    /// Removes the value for the given id.
+    ///
+    /// This shifts every later entry down to preserve the order of the rest of the
+    /// map, so it is O(n).
+    pub fn remove(&mut self, id: U64Id) -> Option<V> {
+        let index = self.indices.remove(&id)?;
+        let (_, value) = self.entries.remove(index);
+        for (id, _) in &self.entries[index..] {
+            *self.indices.get_mut(id).unwrap() -= 1;
+        }
+
+        Some(value)
     }
 
-    /// Returns the inner value.
-    pub const fn inner(self) -> u64 {
-        self.0
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks if the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over the entries in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (U64Id, &V)> {
+        self.entries.iter().map(|(id, value)| (*id, value))
+    }
+}
+
+impl<V> Default for OrderedU64IdMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for SharedU64IdGen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An endless stream of random ids, which owns its rng so it can be kept around
+/// as a field and pulled from on demand. Like [U64Id::new], it never yields `NULL`.
+#[cfg(feature = "rand")]
+#[derive(Debug, Clone)]
+pub struct IdStream<R: rand::Rng> {
+    rng: R,
+}
+
+#[cfg(feature = "rand")]
+impl<R: rand::Rng> IdStream<R> {
+    /// Creates a new stream which draws from the given rng.
+    pub fn new(rng: R) -> Self {
+        Self { rng }
+    }
+}
+
+/// Draws from `thread_rng`, like [U64Id::new].
+#[cfg(feature = "rand")]
+impl Default for IdStream<rand::rngs::ThreadRng> {
+    fn default() -> Self {
+        Self::new(rand::thread_rng())
     }
 }
 
+#[cfg(feature = "rand")]
+impl<R: rand::Rng> Iterator for IdStream<R> {
+    type Item = U64Id;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(U64Id::from_rng(&mut self.rng))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<R: rand::Rng> core::iter::FusedIterator for IdStream<R> {}
+
 #[cfg(feature = "rand")]
 impl Default for U64Id {
     fn default() -> Self {
@@ -48,9 +1486,91 @@ impl Default for U64Id {
     }
 }
 
+/// Reads an id from exactly 8 big-endian bytes. Any other length is an error.
+impl TryFrom<&[u8]> for U64Id {
+    type Error = U64IdError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        <[u8; 8]>::try_from(value)
+            .map(|bytes| Self(u64::from_be_bytes(bytes)))
+            .map_err(|_| U64IdError::InvalidLength(value.len()))
+    }
+}
+
+/// Offsets an id, for block-based allocation like `base + i`.
+///
+/// This saturates at `u64::MAX` rather than wrapping, so it can never wrap around
+/// to `NULL`; in debug builds, overflowing panics instead. It only returns `NULL`
+/// for `NULL + 0`.
+impl core::ops::Add<u64> for U64Id {
+    type Output = U64Id;
+
+    fn add(self, rhs: u64) -> Self::Output {
+        debug_assert!(
+            self.0.checked_add(rhs).is_some(),
+            "U64Id addition overflowed"
+        );
+
+        Self(self.0.saturating_add(rhs))
+    }
+}
+
+/// Widens a non-zero handle, such as a graphics api handle. This is never `NULL`.
+impl From<core::num::NonZeroU32> for U64Id {
+    fn from(value: core::num::NonZeroU32) -> Self {
+        Self(value.get() as u64)
+    }
+}
+
+/// Widens a non-zero handle. This is never `NULL`.
+impl From<core::num::NonZeroU16> for U64Id {
+    fn from(value: core::num::NonZeroU16) -> Self {
+        Self(value.get() as u64)
+    }
+}
+
+impl From<U64Id> for u128 {
+    fn from(value: U64Id) -> Self {
+        value.0 as u128
+    }
+}
+
+/// Narrows a `u128` back to an id, failing with [U64IdError::Overflow] if it doesn't
+/// fit. `NULL` converts fine, like it does from a `u64`.
+impl TryFrom<u128> for U64Id {
+    type Error = U64IdError;
+
+    fn try_from(value: u128) -> Result<Self, Self::Error> {
+        u64::try_from(value)
+            .map(Self)
+            .map_err(|_| U64IdError::Overflow)
+    }
+}
+
+/// Produces the lowercase hex form used by serde. Unlike `Display`, there is no
+/// leading `*`.
+impl From<U64Id> for String {
+    fn from(value: U64Id) -> Self {
+        let mut buf = [0; 16];
+        hex_str(value.0, &mut buf).to_owned()
+    }
+}
+
+/// Produces a `Value::String` of the lowercase hex form, exactly as serde would.
+#[cfg(feature = "serde_json")]
+impl From<U64Id> for serde_json::Value {
+    fn from(value: U64Id) -> Self {
+        serde_json::Value::String(value.into())
+    }
+}
+
 impl fmt::Display for U64Id {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "*{:x}", self.0)
+        // going through a stack buffer skips the formatting machinery, which matters
+        // for hot logging paths
+        let mut buf = [0; 16];
+        f.write_str("*")?;
+        f.write_str(hex_str(self.0, &mut buf))
     }
 }
 impl fmt::LowerHex for U64Id {
@@ -68,51 +1588,231 @@ impl fmt::UpperHex for U64Id {
     }
 }
 
+/// Serializes as minimal-width lowercase hex. With the `serde-padded` feature, every id
+/// is instead exactly 16 hex digits, so serialized ids sort as text in numeric order.
+/// Deserializing accepts either form regardless.
 #[cfg(feature = "serde")]
-impl serde::Serialize for U64Id {
+impl ::serde::Serialize for U64Id {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
-        S: serde::Serializer,
+        S: ::serde::Serializer,
     {
         // we serialize the number as a string with lowercase hex formatting by default
-        serializer.serialize_str(&format!("{:x}", self.0))
+        let mut buf = [0; 16];
+        #[cfg(not(feature = "serde-padded"))]
+        let hex = hex_str(self.0, &mut buf);
+        #[cfg(feature = "serde-padded")]
+        let hex = padded_hex_str(self.0, &mut buf);
+
+        serializer.serialize_str(hex)
     }
 }
 
 #[cfg(feature = "serde")]
-impl<'de> serde::Deserialize<'de> for U64Id {
+impl<'de> ::serde::Deserialize<'de> for U64Id {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
-        D: serde::Deserializer<'de>,
+        D: ::serde::Deserializer<'de>,
     {
         struct AssetIdVisitor;
-        impl<'de> serde::de::Visitor<'de> for AssetIdVisitor {
+        impl<'de> ::serde::de::Visitor<'de> for AssetIdVisitor {
             type Value = u64;
 
             fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-                formatter.write_str("a hex-encoded integer between 0 and 2^64 - 1")
+                formatter.write_str("a hex string of up to 16 hex digits")
             }
 
             fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
             where
-                E: serde::de::Error,
+                E: ::serde::de::Error,
             {
-                u64::from_str_radix(v, 16).map_err(|_| {
-                    serde::de::Error::invalid_value(serde::de::Unexpected::Str(v), &self)
+                let original = v;
+                // lenient mode forgives surrounding whitespace and a `0x` prefix
+                #[cfg(feature = "serde-lenient")]
+                let v = {
+                    let v = v.trim();
+                    v.strip_prefix("0x")
+                        .or_else(|| v.strip_prefix("0X"))
+                        .unwrap_or(v)
+                };
+
+                // overflow gets its own error so callers can tell "too long" apart from "not hex"
+                u64::from_str_radix(v, 16).map_err(|e| match e.kind() {
+                    core::num::IntErrorKind::PosOverflow => {
+                        ::serde::de::Error::invalid_length(v.len(), &self)
+                    }
+                    _ => ::serde::de::Error::invalid_value(
+                        ::serde::de::Unexpected::Str(original),
+                        &self,
+                    ),
                 })
             }
 
             // we can also deserialize a u64! This can be nice. Yes. It is nice.
             fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
             where
-                E: serde::de::Error,
+                E: ::serde::de::Error,
             {
                 u64::from_str_radix(&v.to_string(), 16).map_err(|_| {
-                    serde::de::Error::invalid_value(serde::de::Unexpected::Unsigned(v), &self)
+                    ::serde::de::Error::invalid_value(::serde::de::Unexpected::Unsigned(v), &self)
                 })
             }
+
+            // sloppy js producers give us floats. we take them only when they're integral
+            // and small enough that no precision was lost, and then treat them like a u64.
+            #[cfg(feature = "serde-lenient")]
+            fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+            where
+                E: ::serde::de::Error,
+            {
+                const MAX_SAFE_INTEGER: f64 = ((1u64 << 53) - 1) as f64;
+
+                if v.fract() != 0.0 || !(0.0..=MAX_SAFE_INTEGER).contains(&v) {
+                    return Err(::serde::de::Error::invalid_value(
+                        ::serde::de::Unexpected::Float(v),
+                        &"an integral float between 0 and 2^53 - 1",
+                    ));
+                }
+
+                self.visit_u64(v as u64)
+            }
+        }
+
+        // in lenient mode, let self-describing formats hand us numbers too
+        #[cfg(feature = "serde-lenient")]
+        if deserializer.is_human_readable() {
+            return deserializer.deserialize_any(AssetIdVisitor).map(U64Id);
         }
 
         deserializer.deserialize_str(AssetIdVisitor).map(U64Id)
     }
 }
+
+/// A borrowed slice of ids which serializes compactly.
+///
+/// In binary formats, the whole slice is written as one run of big-endian bytes with a
+/// single `serialize_bytes` call, rather than as a sequence of hex strings. Human
+/// readable formats still get the usual sequence of hex strings. Deserialize it back
+/// with [PackedIdsBuf].
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackedIds<'a>(pub &'a [U64Id]);
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for PackedIds<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            return serializer.collect_seq(self.0);
+        }
+
+        let bytes: Vec<u8> = self.0.iter().flat_map(|v| v.0.to_be_bytes()).collect();
+        serializer.serialize_bytes(&bytes)
+    }
+}
+
+/// The owned counterpart of [PackedIds], which can be deserialized.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PackedIdsBuf(pub Vec<U64Id>);
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for PackedIdsBuf {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        PackedIds(&self.0).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for PackedIdsBuf {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        struct PackedIdsVisitor;
+        impl<'de> ::serde::de::Visitor<'de> for PackedIdsVisitor {
+            type Value = Vec<U64Id>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a byte string with a length divisible by 8")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+            where
+                E: ::serde::de::Error,
+            {
+                if !v.len().is_multiple_of(8) {
+                    return Err(::serde::de::Error::invalid_length(v.len(), &self));
+                }
+
+                Ok(v.chunks_exact(8)
+                    .map(|chunk| U64Id(u64::from_be_bytes(chunk.try_into().unwrap())))
+                    .collect())
+            }
+
+            // some formats without a native byte type hand bytes over as a sequence
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: ::serde::de::SeqAccess<'de>,
+            {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    bytes.push(byte);
+                }
+
+                self.visit_bytes(&bytes)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            return Vec::<U64Id>::deserialize(deserializer).map(PackedIdsBuf);
+        }
+
+        deserializer
+            .deserialize_bytes(PackedIdsVisitor)
+            .map(PackedIdsBuf)
+    }
+}
+
+// nanoserde follows the same conventions as serde: hex strings in json. binary is
+// the fixed 8 bytes nanoserde uses for a plain u64.
+#[cfg(feature = "nanoserde")]
+impl nanoserde::SerJson for U64Id {
+    fn ser_json(&self, d: usize, s: &mut nanoserde::SerJsonState) {
+        let mut buf = [0; 16];
+        hex_str(self.0, &mut buf).ser_json(d, s)
+    }
+}
+
+#[cfg(feature = "nanoserde")]
+impl nanoserde::DeJson for U64Id {
+    fn de_json(
+        s: &mut nanoserde::DeJsonState,
+        i: &mut core::str::Chars,
+    ) -> Result<Self, nanoserde::DeJsonErr> {
+        let v = String::de_json(s, i)?;
+
+        u64::from_str_radix(&v, 16)
+            .map(U64Id)
+            .map_err(|_| s.err_parse("a hex string of up to 16 hex digits"))
+    }
+}
+
+#[cfg(feature = "nanoserde")]
+impl nanoserde::SerBin for U64Id {
+    fn ser_bin(&self, output: &mut Vec<u8>) {
+        self.0.ser_bin(output)
+    }
+}
+
+#[cfg(feature = "nanoserde")]
+impl nanoserde::DeBin for U64Id {
+    fn de_bin(offset: &mut usize, bytes: &[u8]) -> Result<Self, nanoserde::DeBinErr> {
+        u64::de_bin(offset, bytes).map(U64Id)
+    }
+}
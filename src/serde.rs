@@ -0,0 +1,105 @@
+//! Alternative serde representations for [U64Id], for use with `#[serde(with = "...")]`.
+
+use crate::U64Id;
+
+/// Represents an id as an externally tagged enum, so that `NULL` is explicit rather than
+/// a sentinel hex string. A real id becomes `{"present": "ff"}` and `NULL` becomes
+/// `{"absent": null}`.
+///
+/// Use it with `#[serde(with = "u64_id::serde::tagged")]`. Deserializing a `present`
+/// `NULL` is an error, since absence must be spelled out.
+pub mod tagged {
+    use super::U64Id;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Tagged {
+        Present(U64Id),
+        Absent(()),
+    }
+
+    /// Serializes an id as a tagged enum.
+    pub fn serialize<S>(id: &U64Id, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if id.is_null() {
+            Tagged::Absent(())
+        } else {
+            Tagged::Present(*id)
+        }
+        .serialize(serializer)
+    }
+
+    /// Deserializes an id from a tagged enum.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<U64Id, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Tagged::deserialize(deserializer)? {
+            Tagged::Present(id) if id.is_null() => Err(serde::de::Error::custom(
+                "a present id cannot be null, use `absent` instead",
+            )),
+            Tagged::Present(id) => Ok(id),
+            Tagged::Absent(()) => Ok(U64Id::NULL),
+        }
+    }
+}
+
+/// Represents a `Vec<U64Id>` as a list of hex strings in human readable formats, and as a
+/// single run of bytes in binary formats, avoiding per-element overhead there. This is the
+/// same encoding as [PackedIds](crate::PackedIds).
+///
+/// Use it with `#[serde(with = "u64_id::serde::hex_vec")]`.
+pub mod hex_vec {
+    use super::U64Id;
+    use crate::{PackedIds, PackedIdsBuf};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Serializes a list of ids.
+    pub fn serialize<S>(ids: &[U64Id], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        PackedIds(ids).serialize(serializer)
+    }
+
+    /// Deserializes a list of ids.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<U64Id>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        PackedIdsBuf::deserialize(deserializer).map(|v| v.0)
+    }
+}
+
+/// Represents an id as an uppercase hex string, like `"FF"`, for systems which expect that
+/// casing. Deserializing accepts either case, just like the default representation.
+///
+/// Use it with `#[serde(with = "u64_id::serde::upper_hex")]`.
+pub mod upper_hex {
+    use super::U64Id;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    /// Serializes an id as uppercase hex.
+    pub fn serialize<S>(id: &U64Id, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut buf = [0; 16];
+        let len = crate::hex_str(id.inner(), &mut buf).len();
+        buf[..len].make_ascii_uppercase();
+
+        // uppercasing ascii hex digits leaves them ascii
+        serializer.serialize_str(core::str::from_utf8(&buf[..len]).unwrap())
+    }
+
+    /// Deserializes an id from hex of either case.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<U64Id, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        U64Id::deserialize(deserializer)
+    }
+}
@@ -0,0 +1,22 @@
+//! `wasm-bindgen` glue, exposing [U64Id] to JavaScript as a class.
+//!
+//! Ids cross into JS as hex strings. A JS `Number` only holds integers exactly up to
+//! 2^53, so passing the raw `u64` as a number will silently corrupt large ids.
+
+use crate::U64Id;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+impl U64Id {
+    /// Formats the id as plain lowercase hex, for handing to JS.
+    #[wasm_bindgen(js_name = toJsString)]
+    pub fn to_js_string(&self) -> String {
+        String::from(*self)
+    }
+
+    /// Parses an id from the hex made by `to_js_string`, throwing in JS on bad input.
+    #[wasm_bindgen(js_name = fromJsString)]
+    pub fn from_js_string(s: &str) -> Result<U64Id, JsValue> {
+        crate::parse_hex(s).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}